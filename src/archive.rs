@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use tar::EntryType;
+
+/// One member of a tar archive, carrying just enough information to be
+/// rendered through the same columns `-l` prints for entries on disk.
+pub struct Entry {
+    pub path: PathBuf,
+    pub mode: u32,
+    pub size: u64,
+    pub mtime: u64,
+    pub type_char: char,
+    pub link_target: Option<PathBuf>,
+}
+
+/// Whether `path`'s extension marks it as a tar archive we know how to
+/// inspect.
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// List the members of the tar archive at `path`, transparently
+/// decompressing it first if it's gzipped.
+pub fn list_entries(path: &Path) -> Result<Vec<Entry>> {
+    let file = File::open(path).context(format!("cannot access '{}'", path.display()))?;
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+    let reader: Box<dyn Read> = if name.ends_with(".gz") || name.ends_with(".tgz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for entry in archive
+        .entries()
+        .context(format!("cannot read archive '{}'", path.display()))?
+    {
+        let entry = entry?;
+        let header = entry.header();
+
+        let type_char = match header.entry_type() {
+            EntryType::Directory => 'd',
+            EntryType::Symlink => 'l',
+            _ => '-',
+        };
+
+        entries.push(Entry {
+            path: entry.path()?.into_owned(),
+            mode: header.mode().unwrap_or(0),
+            size: header.size().unwrap_or(0),
+            mtime: header.mtime().unwrap_or(0),
+            type_char,
+            link_target: entry.link_name()?.map(|target| target.into_owned()),
+        });
+    }
+
+    Ok(entries)
+}
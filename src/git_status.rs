@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use git2::{Repository, Status};
+
+/// Resolves and caches the enclosing Git repository per directory, so
+/// `--git` doesn't reopen it for every entry it lists.
+#[derive(Default)]
+pub struct GitStatusCache {
+    repos: HashMap<PathBuf, Option<Repository>>,
+}
+
+impl GitStatusCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Two-character status column for `path` (inside `dir`): staged state
+    /// then worktree state, e.g. `"M-"`, `"??"`, `"!!"`, or `"--"` when
+    /// clean. Returns `"  "` if `dir` isn't inside a Git work tree, or if
+    /// `path`'s parent directory can't be resolved or `path` sits outside it.
+    pub fn status_for(&mut self, dir: &Path, path: &Path) -> Result<String> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+        let repo = self
+            .repos
+            .entry(dir.clone())
+            .or_insert_with(|| Repository::discover(&dir).ok());
+
+        let Some(repo) = repo else {
+            return Ok("  ".to_string());
+        };
+
+        let Some(workdir) = repo.workdir() else {
+            return Ok("  ".to_string());
+        };
+
+        let Some(relative) = resolve_relative(workdir, path) else {
+            return Ok("  ".to_string());
+        };
+
+        let status = repo.status_file(&relative).unwrap_or(Status::CURRENT);
+        Ok(status_chars(status))
+    }
+}
+
+/// Resolve `path` to an absolute path without dereferencing its final
+/// component - so a symlink entry resolves to its own tracked path rather
+/// than its target - then express it relative to `workdir`. Returns `None`
+/// if the parent directory can't be resolved (e.g. dangling symlink target)
+/// or `path` doesn't sit inside `workdir`.
+fn resolve_relative(workdir: &Path, path: &Path) -> Option<PathBuf> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name()?;
+
+    let absolute_parent = parent.canonicalize().ok()?;
+    let absolute = absolute_parent.join(file_name);
+
+    absolute.strip_prefix(workdir).ok().map(Path::to_path_buf)
+}
+
+/// Render a `Status` bitset as two status characters for one path: index
+/// state then worktree state, with `-` where that side is unchanged.
+fn status_chars(status: Status) -> String {
+    if status.contains(Status::IGNORED) {
+        return "!!".to_string();
+    }
+    if status.contains(Status::WT_NEW) {
+        return "??".to_string();
+    }
+
+    let index = if status.contains(Status::INDEX_NEW) {
+        'A'
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        'M'
+    } else if status.contains(Status::INDEX_DELETED) {
+        'D'
+    } else if status.contains(Status::INDEX_RENAMED) {
+        'R'
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        'T'
+    } else {
+        '-'
+    };
+
+    let worktree = if status.contains(Status::WT_MODIFIED) {
+        'M'
+    } else if status.contains(Status::WT_DELETED) {
+        'D'
+    } else if status.contains(Status::WT_RENAMED) {
+        'R'
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        'T'
+    } else {
+        '-'
+    };
+
+    format!("{index}{worktree}")
+}
@@ -1,13 +1,20 @@
+mod archive;
+mod git_status;
+
 use anyhow::{Context, Result};
 use byte_unit::Byte;
 use chrono::{Duration, Local, TimeZone};
 use clap::Parser;
 use colored::*;
+use git_status::GitStatusCache;
+use std::cmp::Reverse;
+use std::ffi::OsString;
 use std::fs::{self, Metadata};
 #[cfg(target_os = "linux")]
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
+use terminal_size::{terminal_size, Width};
 #[cfg(target_os = "linux")]
 use users::{get_group_by_gid, get_user_by_uid};
 
@@ -33,6 +40,136 @@ struct Args {
     /// print sizes exactly in bytes
     #[arg(short = 'n', long = "not-human-readable")]
     not_human_readable: bool,
+
+    /// sort by modification time, newest first
+    #[arg(short = 't')]
+    sort_time: bool,
+
+    /// sort by file size, largest first
+    #[arg(short = 'S')]
+    sort_size: bool,
+
+    /// sort by file extension
+    #[arg(short = 'X')]
+    sort_extension: bool,
+
+    /// do not sort, list entries in directory order
+    #[arg(short = 'U')]
+    no_sort: bool,
+
+    /// reverse the sort order
+    #[arg(short = 'r', long = "reverse")]
+    reverse: bool,
+
+    /// list subdirectories recursively
+    #[arg(short = 'R', long = "recursive")]
+    recursive: bool,
+
+    /// list entries by columns, packed to fit the terminal width
+    #[arg(short = 'C', long = "columns")]
+    columns: bool,
+
+    /// show Git status columns next to each entry (with -l)
+    #[arg(long = "git")]
+    git: bool,
+
+    /// inspect .tar/.tar.gz archives as if they were directories
+    #[arg(long = "tree")]
+    tree: bool,
+}
+
+/// How to order entries before printing them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Name,
+    Time,
+    Size,
+    Extension,
+    None,
+}
+
+impl SortBy {
+    /// Pick the sort order requested by the flags in `args`.
+    ///
+    /// `-U` wins over the other sort flags, which are otherwise mutually
+    /// exclusive in practice; the first one that matches is used.
+    fn from_args(args: &Args) -> SortBy {
+        if args.no_sort {
+            SortBy::None
+        } else if args.sort_time {
+            SortBy::Time
+        } else if args.sort_size {
+            SortBy::Size
+        } else if args.sort_extension {
+            SortBy::Extension
+        } else {
+            SortBy::Name
+        }
+    }
+}
+
+/// Comparison key for one entry under a given `SortBy`.
+///
+/// Computed once per entry so sorting doesn't re-derive it on every
+/// comparison. Time and size sort descending (newest/largest first), so
+/// they wrap the underlying value in `Reverse`; every variant falls back
+/// to the entry name so ties are broken deterministically.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Name(OsString),
+    Time(Reverse<i64>, OsString),
+    Size(Reverse<u64>, OsString),
+    Extension(OsString, OsString),
+}
+
+/// Compute the sort key for `path`/`metadata` under `sort_by`.
+fn key(sort_by: SortBy, path: &Path, metadata: &Metadata) -> SortKey {
+    let name = path.file_name().unwrap_or_default().to_os_string();
+
+    match sort_by {
+        SortBy::Name | SortBy::None => SortKey::Name(name),
+        SortBy::Time => {
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0);
+            SortKey::Time(Reverse(mtime), name)
+        }
+        SortBy::Size => SortKey::Size(Reverse(metadata.len()), name),
+        SortBy::Extension => {
+            let extension = path.extension().unwrap_or_default().to_os_string();
+            SortKey::Extension(extension, name)
+        }
+    }
+}
+
+/// Sort `paths` according to `sort_by`, stat-ing each one to build the key,
+/// then reverse the result if `reverse` is set.
+fn sort_paths(paths: &mut [PathBuf], sort_by: SortBy, reverse: bool) {
+    if sort_by != SortBy::None {
+        paths.sort_by_cached_key(|path| match fs::symlink_metadata(path) {
+            Ok(metadata) => key(sort_by, path, &metadata),
+            Err(_) => SortKey::Name(path.file_name().unwrap_or_default().to_os_string()),
+        });
+    }
+
+    if reverse {
+        paths.reverse();
+    }
+}
+
+/// Sort `entries` (already paired with their metadata so we don't re-`stat`
+/// them) according to `sort_by`, then reverse the result if `reverse` is set.
+fn sort_entries(entries: &mut [(PathBuf, Metadata)], sort_by: SortBy, reverse: bool) {
+    if sort_by != SortBy::None {
+        entries.sort_by_cached_key(|(path, metadata)| key(sort_by, path, metadata));
+    }
+
+    if reverse {
+        entries.reverse();
+    }
 }
 
 fn main() {
@@ -47,7 +184,7 @@ fn main() {
         args.files.push(PathBuf::from("."));
     }
 
-    args.files.sort_unstable();
+    sort_paths(&mut args.files, SortBy::from_args(&args), args.reverse);
 
     if let Err(e) = list_files(&args) {
         eprintln!("{} {:?}", "ls - error:".red(), e);
@@ -59,46 +196,113 @@ fn main() {
 ///
 /// If the file is a directory, info for each entry is printed.
 fn list_files(args: &Args) -> Result<()> {
+    let sort_by = SortBy::from_args(args);
+    let mut git_cache = GitStatusCache::new();
+
     for filename in &args.files {
         if filename.is_dir() && !args.directory {
-            println!("{}:", filename.to_str().unwrap_or_default().blue().bold());
+            list_dir(args, filename, sort_by, &mut git_cache)?;
+        } else {
+            file_info(args, filename, &mut git_cache)?;
+        }
+    }
 
-            let mut entries: Vec<PathBuf> = fs::read_dir(filename)
-                .context(format!("Failed to read dir {}", filename.display()))?
-                .filter_map(|entry| entry.ok())
-                .map(|entry| entry.path())
-                .collect();
-            entries.sort_unstable();
+    Ok(())
+}
+
+/// Print the entries of `dir`, then, if `-R` was given, recurse into its
+/// subdirectories (skipping symlinks, to avoid loops), each with its own
+/// `path:` header preceded by a blank line.
+fn list_dir(
+    args: &Args,
+    dir: &Path,
+    sort_by: SortBy,
+    git_cache: &mut GitStatusCache,
+) -> Result<()> {
+    println!("{}:", dir.to_str().unwrap_or_default().blue().bold());
+
+    let mut entries: Vec<(PathBuf, Metadata)> = fs::read_dir(dir)
+        .context(format!("Failed to read dir {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let metadata = fs::symlink_metadata(&path).ok()?;
+            Some((path, metadata))
+        })
+        .collect();
+    sort_entries(&mut entries, sort_by, args.reverse);
+
+    let visible: Vec<&(PathBuf, Metadata)> = entries
+        .iter()
+        .filter(|(path, _)| !is_hidden(args, entry_name(path)))
+        .collect();
+
+    if !args.long && args.columns {
+        let names: Vec<&str> = visible.iter().map(|(path, _)| entry_name(path)).collect();
+        print_grid(&names);
+    } else {
+        for (path, _metadata) in &visible {
+            file_info(args, path, git_cache)?;
+        }
+    }
 
-            for path in entries {
-                file_info(args, &path)?;
+    if args.recursive {
+        for (path, metadata) in &visible {
+            if metadata.is_dir() && !metadata.is_symlink() {
+                println!();
+                list_dir(args, path, sort_by, git_cache)?;
             }
-        } else {
-            file_info(args, filename)?;
         }
     }
 
     Ok(())
 }
 
+/// Pack `names` into columns sized to fit the terminal width (falling back
+/// to 80 columns when it can't be queried), filling column-major like GNU
+/// `ls`.
+fn print_grid(names: &[&str]) {
+    if names.is_empty() {
+        return;
+    }
+
+    let term_width = terminal_size()
+        .map(|(Width(width), _)| width as usize)
+        .unwrap_or(80);
+
+    let longest = names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+    let col_width = longest + 2;
+    let columns = (term_width / col_width).clamp(1, names.len());
+    let rows = names.len().div_ceil(columns);
+
+    for row in 0..rows {
+        for col in 0..columns {
+            let Some(name) = names.get(col * rows + row) else {
+                continue;
+            };
+
+            if col + 1 == columns {
+                print!("{name}");
+            } else {
+                print!("{name:col_width$}");
+            }
+        }
+        println!();
+    }
+}
+
 /// Print info about file specified by path,
 /// according to flags from args.
-fn file_info(args: &Args, path: &Path) -> Result<()> {
-    let filename = if path == PathBuf::from(".") {
-        "."
-    } else if path == PathBuf::from("..") {
-        ".."
-    } else {
-        path.file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default()
-    };
+fn file_info(args: &Args, path: &Path, git_cache: &mut GitStatusCache) -> Result<()> {
+    if args.tree && path.is_file() && archive::is_archive(path) {
+        return print_archive(args, path);
+    }
+
+    let filename = entry_name(path);
 
     let metadata = fs::symlink_metadata(path).context(format!("cannot acces '{}'", filename))?;
 
-    if !args.all && filename != "." && filename != ".." && filename.starts_with('.') {
-        // Skip hidden files
+    if is_hidden(args, filename) {
         return Ok(());
     }
 
@@ -109,15 +313,16 @@ fn file_info(args: &Args, path: &Path) -> Result<()> {
         return Ok(());
     }
 
-    let size = if args.not_human_readable {
-        metadata.len().to_string()
+    let git_status = if args.git {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        format!("{} ", git_cache.status_for(dir, path)?)
     } else {
-        let bytes = Byte::from_bytes(metadata.len() as u128);
-        bytes.get_appropriate_unit(true).to_string()
+        String::new()
     };
 
+    let size = format_size(metadata.len(), !args.not_human_readable);
+
     let modified = get_modified_str(&metadata)?;
-    let file_type = get_file_type_str(&metadata);
 
     let target_file = if path.is_symlink() {
         format!(" -> {}", fs::read_link(path)?.to_str().unwrap_or_default())
@@ -127,14 +332,17 @@ fn file_info(args: &Args, path: &Path) -> Result<()> {
 
     #[cfg(target_os = "windows")]
     {
+        let attrs = get_attr_str(&metadata);
+
         println!(
-            "{} {:11} {} {}{}{}",
-            file_type, size, modified, filename, suffix, target
+            "{}{} {:>10} {} {}{}{}",
+            git_status, attrs, size, modified, filename, suffix, target_file
         );
     }
 
     #[cfg(target_os = "linux")]
     {
+        let file_type = get_file_type_str(&metadata);
         let mode = get_mode_str(metadata.mode());
         let nlink = metadata.nlink();
         let user = match get_user_by_uid(metadata.uid()) {
@@ -147,47 +355,194 @@ fn file_info(args: &Args, path: &Path) -> Result<()> {
         };
 
         println!(
-            "{}{} {} {:8} {:8} {:>10} {} {}{}{}",
-            file_type, mode, nlink, user, group, size, modified, filename, suffix, target_file
+            "{}{}{} {} {:8} {:8} {:>10} {} {}{}{}",
+            git_status, file_type, mode, nlink, user, group, size, modified, filename, suffix, target_file
         );
     }
 
     Ok(())
 }
 
+/// Print the members of the tar archive at `path` as a header line followed
+/// by one line per member, feeding each through `print_archive_entry` the
+/// same way `list_dir` feeds on-disk entries through `file_info`.
+fn print_archive(args: &Args, path: &Path) -> Result<()> {
+    println!("{}:", path.to_str().unwrap_or_default().blue().bold());
+
+    for entry in archive::list_entries(path)? {
+        print_archive_entry(args, &entry)?;
+    }
+
+    Ok(())
+}
+
+/// Print one synthesized archive member, reusing the same mode/size/mtime
+/// formatting `file_info` uses for on-disk files.
+#[cfg(target_os = "linux")]
+fn print_archive_entry(args: &Args, entry: &archive::Entry) -> Result<()> {
+    let filename = entry.path.to_str().unwrap_or_default();
+    let suffix = if entry.type_char == 'd' { "/" } else { "" };
+
+    if !args.long {
+        println!("{}{}", filename, suffix);
+        return Ok(());
+    }
+
+    let mode = get_mode_str(entry.mode);
+    let size = format_size(entry.size, !args.not_human_readable);
+    let modified = format_mtime(entry.mtime as i64)?;
+    let target_file = match &entry.link_target {
+        Some(target) => format!(" -> {}", target.to_str().unwrap_or_default()),
+        None => String::new(),
+    };
+
+    println!(
+        "{}{} {:>10} {} {}{}{}",
+        entry.type_char, mode, size, modified, filename, suffix, target_file
+    );
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn print_archive_entry(args: &Args, entry: &archive::Entry) -> Result<()> {
+    let filename = entry.path.to_str().unwrap_or_default();
+    let suffix = if entry.type_char == 'd' { "/" } else { "" };
+
+    if !args.long {
+        println!("{}{}", filename, suffix);
+        return Ok(());
+    }
+
+    let size = format_size(entry.size, !args.not_human_readable);
+    let modified = format_mtime(entry.mtime as i64)?;
+
+    println!("{} {:>10} {} {}{}", entry.type_char, size, modified, filename, suffix);
+
+    Ok(())
+}
+
 #[cfg(target_os = "linux")]
 fn get_mode_str(mode: u32) -> String {
     let mut mode_str = String::new();
 
     mode_str.push(if mode & 0o400 > 0 { 'r' } else { '-' });
     mode_str.push(if mode & 0o200 > 0 { 'w' } else { '-' });
-    mode_str.push(if mode & 0o100 > 0 { 'x' } else { '-' });
+    mode_str.push(exec_or_special(mode & 0o100 > 0, mode & 0o4000 > 0, 's', 'S'));
 
     mode_str.push(if mode & 0o040 > 0 { 'r' } else { '-' });
     mode_str.push(if mode & 0o020 > 0 { 'w' } else { '-' });
-    mode_str.push(if mode & 0o010 > 0 { 'x' } else { '-' });
+    mode_str.push(exec_or_special(mode & 0o010 > 0, mode & 0o2000 > 0, 's', 'S'));
 
     mode_str.push(if mode & 0o004 > 0 { 'r' } else { '-' });
     mode_str.push(if mode & 0o002 > 0 { 'w' } else { '-' });
-    mode_str.push(if mode & 0o001 > 0 { 'x' } else { '-' });
+    mode_str.push(exec_or_special(mode & 0o001 > 0, mode & 0o1000 > 0, 't', 'T'));
 
     mode_str
 }
 
-fn get_file_type_str(metadata: &Metadata) -> String {
-    if metadata.is_dir() {
-        "d".to_string()
-    } else if metadata.is_symlink() {
-        "l".to_string()
+/// Render one execute slot, folding in a special bit (setuid/setgid/sticky):
+/// lowercase when execute is also set, uppercase when it isn't.
+#[cfg(target_os = "linux")]
+fn exec_or_special(exec: bool, special: bool, lower: char, upper: char) -> char {
+    match (exec, special) {
+        (true, true) => lower,
+        (false, true) => upper,
+        (true, false) => 'x',
+        (false, false) => '-',
+    }
+}
+
+/// The name `ls` should print for `path`: `.`/`..` verbatim, otherwise the
+/// final path component.
+fn entry_name(path: &Path) -> &str {
+    if path == Path::new(".") {
+        "."
+    } else if path == Path::new("..") {
+        ".."
     } else {
-        "-".to_string()
+        path.file_name().unwrap_or_default().to_str().unwrap_or_default()
+    }
+}
+
+/// Whether `filename` should be skipped because it's hidden and `-a` wasn't
+/// given. Hidden means dot-prefixed (excluding `.`/`..`), plus
+/// underscore-prefixed on Windows, by convention there.
+fn is_hidden(args: &Args, filename: &str) -> bool {
+    if args.all || filename == "." || filename == ".." {
+        return false;
     }
+
+    #[cfg(target_os = "windows")]
+    let hidden = filename.starts_with('.') || filename.starts_with('_');
+    #[cfg(not(target_os = "windows"))]
+    let hidden = filename.starts_with('.');
+
+    hidden
+}
+
+/// Full POSIX file-type character, derived from the `S_IFMT` bits of the
+/// mode so block/char devices, FIFOs and sockets are distinguished from
+/// plain files.
+#[cfg(target_os = "linux")]
+fn get_file_type_str(metadata: &Metadata) -> String {
+    let file_type = match metadata.mode() & 0o170000 {
+        0o040000 => "d",
+        0o120000 => "l",
+        0o060000 => "b",
+        0o020000 => "c",
+        0o010000 => "p",
+        0o140000 => "s",
+        _ => "-",
+    };
+
+    file_type.to_string()
+}
+
+/// DOS attribute string shown in place of the Unix mode column: directory,
+/// read-only, hidden, system and archive bits, in that order.
+#[cfg(target_os = "windows")]
+fn get_attr_str(metadata: &Metadata) -> String {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+    const FILE_ATTRIBUTE_ARCHIVE: u32 = 0x20;
+
+    let attrs = metadata.file_attributes();
+    let mut attr_str = String::new();
+
+    attr_str.push(if attrs & FILE_ATTRIBUTE_DIRECTORY > 0 { 'd' } else { '-' });
+    attr_str.push(if attrs & FILE_ATTRIBUTE_READONLY > 0 { 'r' } else { '-' });
+    attr_str.push(if attrs & FILE_ATTRIBUTE_HIDDEN > 0 { 'h' } else { '-' });
+    attr_str.push(if attrs & FILE_ATTRIBUTE_SYSTEM > 0 { 's' } else { '-' });
+    attr_str.push(if attrs & FILE_ATTRIBUTE_ARCHIVE > 0 { 'a' } else { '-' });
+
+    attr_str
 }
 
 fn get_modified_str(metadata: &Metadata) -> Result<String> {
     let std_duration = metadata.modified()?.duration_since(UNIX_EPOCH)?;
     let duration = Duration::from_std(std_duration)?;
-    let datetime = Local.timestamp_opt(duration.num_seconds(), 0).unwrap();
+    format_mtime(duration.num_seconds())
+}
+
+/// Render a Unix timestamp the way `-l` shows modification times.
+fn format_mtime(timestamp: i64) -> Result<String> {
+    let datetime = Local.timestamp_opt(timestamp, 0).unwrap();
     Ok(datetime.format("%b %d %H:%M").to_string())
 }
 
+/// Render a byte count the way `-l` shows file sizes: human-readable unless
+/// `-n` was given.
+fn format_size(len: u64, human_readable: bool) -> String {
+    if human_readable {
+        let bytes = Byte::from_bytes(len as u128);
+        bytes.get_appropriate_unit(true).to_string()
+    } else {
+        len.to_string()
+    }
+}
+